@@ -0,0 +1,95 @@
+//! On-disk response cache for rhyme lookups, keyed by query parameters.
+//!
+//! Loosely modeled on servo's `http_cache`: entries are stored as plain
+//! files under the platform cache dir and are considered fresh until a
+//! configurable TTL elapses, at which point they're treated as a miss.
+
+use std::{
+    collections::hash_map::DefaultHasher,
+    hash::{Hash, Hasher},
+    path::PathBuf,
+    time::{Duration, SystemTime},
+};
+
+use directories::ProjectDirs;
+
+use crate::RifmeOptions;
+
+/// Cache-related settings derived from CLI flags.
+#[derive(Clone, Copy, Debug)]
+pub struct CacheConfig {
+    pub enabled: bool,
+    pub ttl: Duration,
+}
+
+fn cache_dir() -> Option<PathBuf> {
+    ProjectDirs::from("net", "rifme", "rifme").map(|dirs| dirs.cache_dir().to_path_buf())
+}
+
+fn cache_key(word: &str, options: &RifmeOptions) -> String {
+    let mut hasher = DefaultHasher::new();
+    word.hash(&mut hasher);
+    options.syllables.hash(&mut hasher);
+    options
+        .part
+        .as_ref()
+        .map(|part| part.clone() as i8)
+        .hash(&mut hasher);
+    options.emphasis.hash(&mut hasher);
+    format!("{:016x}", hasher.finish())
+}
+
+/// Returns the cached rhymes for `(word, options)` if a fresh entry exists.
+pub fn get(word: &str, options: &RifmeOptions, config: &CacheConfig) -> Option<Vec<String>> {
+    if !config.enabled {
+        return None;
+    }
+    let path = cache_dir()?.join(cache_key(word, options));
+    let modified = std::fs::metadata(&path).ok()?.modified().ok()?;
+    if SystemTime::now().duration_since(modified).ok()? > config.ttl {
+        return None;
+    }
+    let contents = std::fs::read_to_string(&path).ok()?;
+    Some(contents.lines().map(str::to_string).collect())
+}
+
+/// Stores `rhymes` for `(word, options)`, silently skipping on I/O failure.
+pub fn put(word: &str, options: &RifmeOptions, rhymes: &[String], config: &CacheConfig) {
+    if !config.enabled {
+        return;
+    }
+    let Some(dir) = cache_dir() else {
+        return;
+    };
+    if std::fs::create_dir_all(&dir).is_err() {
+        return;
+    }
+    let _ = std::fs::write(dir.join(cache_key(word, options)), rhymes.join("\n"));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cache_key_is_deterministic_and_parameter_sensitive() {
+        let options = RifmeOptions {
+            syllables: Some(2),
+            part: None,
+            emphasis: Some(0),
+        };
+        assert_eq!(cache_key("привет", &options), cache_key("привет", &options));
+
+        let other_word = cache_key("мир", &options);
+        assert_ne!(cache_key("привет", &options), other_word);
+
+        let other_syllables = RifmeOptions {
+            syllables: Some(3),
+            ..options.clone()
+        };
+        assert_ne!(
+            cache_key("привет", &options),
+            cache_key("привет", &other_syllables)
+        );
+    }
+}
@@ -0,0 +1,77 @@
+//! Config file support: defaults and named profiles for rhyme options.
+//!
+//! Read from `config.toml` in the platform config dir. The top-level table
+//! supplies defaults; a `[profiles.<name>]` table overrides them when
+//! selected with `--profile <name>`. CLI flags always win over either.
+
+use std::{collections::HashMap, path::PathBuf};
+
+use directories::ProjectDirs;
+use serde::Deserialize;
+
+use crate::PartOfSpeech;
+
+#[derive(Deserialize, Default, Clone)]
+pub struct Profile {
+    pub syllables: Option<i8>,
+    pub part: Option<PartOfSpeech>,
+    pub emphasis: Option<i8>,
+}
+
+#[derive(Deserialize, Default)]
+pub struct Config {
+    #[serde(flatten)]
+    pub default: Profile,
+    #[serde(default)]
+    pub profiles: HashMap<String, Profile>,
+}
+
+impl Config {
+    /// The default profile, or the named one if `name` is given. Errors if
+    /// `name` doesn't match any `[profiles.<name>]` table, so a typo can't
+    /// silently discard the user's configured defaults.
+    pub fn profile(&self, name: Option<&str>) -> Result<Profile, String> {
+        match name {
+            Some(name) => self
+                .profiles
+                .get(name)
+                .cloned()
+                .ok_or_else(|| format!("no such profile: {}", name)),
+            None => Ok(self.default.clone()),
+        }
+    }
+}
+
+fn config_path() -> Option<PathBuf> {
+    ProjectDirs::from("net", "rifme", "rifme").map(|dirs| dirs.config_dir().join("config.toml"))
+}
+
+/// Loads the config file, falling back to an empty config if it's missing
+/// or malformed.
+pub fn load() -> Config {
+    let Some(path) = config_path() else {
+        return Config::default();
+    };
+    let Ok(contents) = std::fs::read_to_string(path) else {
+        return Config::default();
+    };
+    toml::from_str(&contents).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unknown_profile_errors_instead_of_falling_back_silently() {
+        let config = Config::default();
+        assert!(config.profile(Some("does-not-exist")).is_err());
+    }
+
+    #[test]
+    fn no_profile_name_uses_top_level_defaults() {
+        let mut config = Config::default();
+        config.default.syllables = Some(3);
+        assert_eq!(config.profile(None).unwrap().syllables, Some(3));
+    }
+}
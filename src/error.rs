@@ -0,0 +1,19 @@
+//! Error types surfaced to the user instead of panicking.
+
+use std::fmt;
+
+/// A successfully received response whose status wasn't a success. This
+/// includes retryable statuses (429, 5xx) still returned once the retries
+/// are used up.
+#[derive(Debug)]
+pub struct HttpStatusError {
+    pub status: reqwest::StatusCode,
+}
+
+impl fmt::Display for HttpStatusError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "rifme.net returned an error status: {}", self.status)
+    }
+}
+
+impl std::error::Error for HttpStatusError {}
@@ -0,0 +1,119 @@
+//! Local filtering, deduplication and ranking over already-fetched rhymes.
+//!
+//! This is pure post-processing: it never touches the network, it just
+//! narrows/reorders the `Vec<String>` each `RhymeGroup` already holds.
+
+use std::collections::HashSet;
+
+use crate::output::RhymeGroup;
+
+#[derive(Clone, Debug, clap::ValueEnum)]
+pub enum SortOrder {
+    Length,
+    Alpha,
+}
+
+#[derive(Default)]
+pub struct FilterOptions {
+    pub contains: Option<String>,
+    pub min_len: Option<usize>,
+    pub max_len: Option<usize>,
+    pub exclude: Vec<String>,
+    pub dedupe: bool,
+    pub sort: Option<SortOrder>,
+    pub limit: Option<usize>,
+}
+
+/// Filters, dedupes, sorts and limits `groups`.
+///
+/// Filtering and dedupe preserve the syllable-count grouping. `--sort` is a
+/// ranking over the whole result, not a per-bucket one, so sorting collapses
+/// the groups into a single unlabeled one — otherwise concatenating
+/// independently-sorted buckets wouldn't actually be sorted overall.
+pub fn apply(mut groups: Vec<RhymeGroup>, options: &FilterOptions) -> Vec<RhymeGroup> {
+    let mut seen = HashSet::new();
+    for group in &mut groups {
+        group.rhymes.retain(|rhyme| {
+            if let Some(contains) = &options.contains {
+                if !rhyme.contains(contains.as_str()) {
+                    return false;
+                }
+            }
+            let len = rhyme.chars().count();
+            if let Some(min_len) = options.min_len {
+                if len < min_len {
+                    return false;
+                }
+            }
+            if let Some(max_len) = options.max_len {
+                if len > max_len {
+                    return false;
+                }
+            }
+            if options.exclude.iter().any(|excluded| excluded == rhyme) {
+                return false;
+            }
+            if options.dedupe && !seen.insert(rhyme.clone()) {
+                return false;
+            }
+            true
+        });
+    }
+
+    let mut groups = match &options.sort {
+        Some(sort) => {
+            let mut rhymes: Vec<String> = groups.into_iter().flat_map(|group| group.rhymes).collect();
+            match sort {
+                SortOrder::Length => rhymes.sort_by_key(|rhyme| rhyme.chars().count()),
+                SortOrder::Alpha => rhymes.sort(),
+            }
+            vec![RhymeGroup {
+                syllables: None,
+                rhymes,
+            }]
+        }
+        None => groups,
+    };
+
+    if let Some(limit) = options.limit {
+        let mut remaining = limit;
+        for group in &mut groups {
+            if remaining == 0 {
+                group.rhymes.clear();
+                continue;
+            }
+            if group.rhymes.len() > remaining {
+                group.rhymes.truncate(remaining);
+            }
+            remaining -= group.rhymes.len();
+        }
+    }
+
+    groups
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn alpha_sort_is_global_not_per_bucket() {
+        let groups = vec![
+            RhymeGroup {
+                syllables: Some(1),
+                rhymes: vec!["bb".into(), "dd".into()],
+            },
+            RhymeGroup {
+                syllables: Some(2),
+                rhymes: vec!["aa".into(), "cc".into()],
+            },
+        ];
+        let options = FilterOptions {
+            sort: Some(SortOrder::Alpha),
+            ..Default::default()
+        };
+        let out = apply(groups, &options);
+        let all: Vec<_> = out.iter().flat_map(|g| g.rhymes.iter().cloned()).collect();
+        assert_eq!(all, vec!["aa", "bb", "cc", "dd"]);
+    }
+}
@@ -1,14 +1,31 @@
 #![allow(unused)]
 
-use std::{env, error::Error};
+mod cache;
+mod config;
+mod error;
+mod filter;
+mod output;
+mod retry;
 
+use std::{env, error::Error, io::Read, path::PathBuf, time::Duration};
+
+use cache::CacheConfig;
+use filter::{FilterOptions, SortOrder};
+use futures::stream::{self, StreamExt};
+use output::{OutputFormat, RhymeGroup};
 use reqwest::header;
 use scraper::{Html, Selector};
 
 const USER_AGENT: &str =
     "Mozilla/5.0 (X11; Linux x86_64; rv:78.0) Gecko/20100101 Firefox/78.0";
 
-#[derive(Clone, Debug, PartialEq, clap::ValueEnum)]
+/// One syllable-count fetch's outcome, tagged with the word it belongs to.
+type IndexedRhymeResult = (usize, Result<RhymeGroup, Box<dyn Error>>);
+
+/// One word's successful groups alongside the errors from its failed buckets.
+type WordRhymeResult = (Vec<RhymeGroup>, Vec<Box<dyn Error>>);
+
+#[derive(Clone, Debug, PartialEq, Hash, serde::Serialize, serde::Deserialize, clap::ValueEnum)]
 enum PartOfSpeech {
     Noun = 1,
     Adj,
@@ -16,7 +33,7 @@ enum PartOfSpeech {
     Other = 0,
 }
 
-#[derive(Default)]
+#[derive(Default, Clone)]
 struct RifmeOptions {
     syllables: Option<i8>,
     part: Option<PartOfSpeech>,
@@ -37,40 +54,57 @@ fn build_cookie(options: RifmeOptions) -> String {
 async fn get_page(
     url: &str,
     options: RifmeOptions,
-) -> Result<String, reqwest::Error> {
+    retries: u32,
+) -> Result<String, Box<dyn Error>> {
     let mut headers = header::HeaderMap::new();
     headers.insert(
         "COOKIE",
-        header::HeaderValue::try_from(build_cookie(options)).unwrap(),
+        header::HeaderValue::try_from(build_cookie(options))?,
     );
     let client = reqwest::Client::builder()
         .user_agent(USER_AGENT)
         .default_headers(headers)
         .build()?;
-    let body = client.get(url).send().await.unwrap().text();
-    return body.await;
+    let response = retry::get_with_retry(&client, url, retries).await?;
+    if !response.status().is_success() {
+        return Err(Box::new(error::HttpStatusError {
+            status: response.status(),
+        }));
+    }
+    Ok(response.text().await?)
 }
 
 fn get_rhymes(doc: Html) -> Result<Vec<String>, Box<dyn Error>> {
     let selector = Selector::parse("li[class=riLi]").unwrap();
     let result = doc
         .select(&selector)
-        .map(|li| li.value().attr("data-w").unwrap().to_string())
-        .collect::<Vec<_>>();
+        .map(|li| {
+            li.value()
+                .attr("data-w")
+                .map(str::to_string)
+                .ok_or_else(|| -> Box<dyn Error> { "rhyme list item missing data-w attribute".into() })
+        })
+        .collect::<Result<Vec<_>, _>>()?;
     return Ok(result);
 }
 
 async fn get_rifme(
     word: String,
     options: RifmeOptions,
+    cache_config: &CacheConfig,
+    retries: u32,
 ) -> Result<Vec<String>, Box<dyn Error>> {
+    if let Some(rhymes) = cache::get(&word, &options, cache_config) {
+        return Ok(rhymes);
+    }
     let mut url = format!("https://rifme.net/r/{}", word,);
     if let Some(emphasis) = options.emphasis {
         url.push_str(&format!("/{}", emphasis));
     }
-    let body = get_page(&url, options).await.unwrap();
+    let body = get_page(&url, options.clone(), retries).await?;
     let doc = Html::parse_document(&body);
-    let rhymes = get_rhymes(doc).unwrap();
+    let rhymes = get_rhymes(doc)?;
+    cache::put(&word, &options, &rhymes, cache_config);
     return Ok(rhymes);
 }
 
@@ -84,8 +118,8 @@ use clap::Parser;
     long_about = None
 )]
 struct Args {
-    /// Word to get rhymes for
-    word: String,
+    /// Word to get rhymes for. Omit to read words from --words-file or stdin
+    word: Option<String>,
 
     /// Number of syllables - any by default, 0 for FULL (may be slow)
     #[arg(short, long, default_value = None)]
@@ -98,40 +132,279 @@ struct Args {
     /// Emphasis number - 0 for last, 1 for 2nd last, etc.
     #[arg(short, long, default_value = None)]
     emphasis: Option<i8>,
+
+    /// Disable the on-disk response cache
+    #[arg(long, default_value_t = false)]
+    no_cache: bool,
+
+    /// How long a cached response stays fresh, in seconds
+    #[arg(long, default_value_t = 3600)]
+    cache_ttl: u64,
+
+    /// Output format
+    #[arg(short, long, value_enum, default_value_t = OutputFormat::Text)]
+    output: OutputFormat,
+
+    /// Named config profile to use for default syllables/part/emphasis
+    #[arg(long)]
+    profile: Option<String>,
+
+    /// Number of times to retry a failed request
+    #[arg(long, default_value_t = 3, value_parser = clap::value_parser!(u32).range(0..=20))]
+    retries: u32,
+
+    /// Read newline-separated words from this file instead of stdin
+    #[arg(long)]
+    words_file: Option<PathBuf>,
+
+    /// Max number of requests in flight across all words/syllable counts
+    #[arg(long, default_value_t = 8, value_parser = clap::value_parser!(u64).range(1..))]
+    concurrency: u64,
+
+    /// Only keep rhymes containing this substring
+    #[arg(long)]
+    contains: Option<String>,
+
+    /// Only keep rhymes with at least this many characters
+    #[arg(long)]
+    min_len: Option<usize>,
+
+    /// Only keep rhymes with at most this many characters
+    #[arg(long)]
+    max_len: Option<usize>,
+
+    /// Exclude this rhyme (repeatable)
+    #[arg(long)]
+    exclude: Vec<String>,
+
+    /// Collapse duplicate rhymes across the syllable-count fan-out
+    #[arg(long, default_value_t = false)]
+    dedupe: bool,
+
+    /// Sort rhymes by length or alphabetically across the whole result
+    /// (merges the per-syllable-count grouping into a single ranked list)
+    #[arg(long, value_enum)]
+    sort: Option<SortOrder>,
+
+    /// Keep at most this many rhymes in total for each input word
+    #[arg(long)]
+    limit: Option<usize>,
+}
+
+impl Args {
+    fn filter_options(&self) -> FilterOptions {
+        FilterOptions {
+            contains: self.contains.to_owned(),
+            min_len: self.min_len,
+            max_len: self.max_len,
+            exclude: self.exclude.to_owned(),
+            dedupe: self.dedupe,
+            sort: self.sort.to_owned(),
+            limit: self.limit,
+        }
+    }
+}
+
+/// Gathers the words to rhyme: the `word` positional if given, otherwise
+/// `--words-file`, otherwise newline-separated words from stdin.
+fn collect_words(args: &Args) -> Result<Vec<String>, Box<dyn Error>> {
+    if let Some(word) = &args.word {
+        return Ok(vec![word.clone()]);
+    }
+    let contents = match &args.words_file {
+        Some(path) => std::fs::read_to_string(path)?,
+        None => {
+            let mut buf = String::new();
+            std::io::stdin().read_to_string(&mut buf)?;
+            buf
+        }
+    };
+    Ok(contents
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(str::to_string)
+        .collect())
+}
+
+/// Regroups the flat, out-of-order `results` from the concurrent
+/// syllable-count fetches back by word, preserving every bucket's outcome.
+/// A failed bucket never displaces a successful one for the same word —
+/// callers decide what to do with the mix of `Ok`/`Err` per word.
+fn group_results_by_word(
+    word_count: usize,
+    results: Vec<IndexedRhymeResult>,
+) -> Vec<Vec<Result<RhymeGroup, Box<dyn Error>>>> {
+    let mut per_word: Vec<Vec<Result<RhymeGroup, Box<dyn Error>>>> =
+        (0..word_count).map(|_| Vec::new()).collect();
+    for (word_index, result) in results {
+        per_word[word_index].push(result);
+    }
+    per_word
+}
+
+/// Splits one word's bucket results into the groups that succeeded (sorted
+/// by syllable count) and the errors from the buckets that didn't.
+fn split_bucket_results(results: Vec<Result<RhymeGroup, Box<dyn Error>>>) -> WordRhymeResult {
+    let mut groups = Vec::new();
+    let mut errors = Vec::new();
+    for result in results {
+        match result {
+            Ok(group) => groups.push(group),
+            Err(err) => errors.push(err),
+        }
+    }
+    groups.sort_by_key(|group| group.syllables);
+    (groups, errors)
 }
 
 #[async_std::main]
 async fn main() {
     let args = Args::parse();
-    let rhymes: Vec<String> = if args.syllables.unwrap_or(-1) > 0 {
-        get_rifme(
-            args.word,
-            RifmeOptions {
-                syllables: args.syllables,
-                part: args.part,
-                emphasis: args.emphasis,
-            },
-        )
-        .await
-        .unwrap()
+    let config = config::load();
+    let profile = match config.profile(args.profile.as_deref()) {
+        Ok(profile) => profile,
+        Err(err) => {
+            eprintln!("error: {}", err);
+            std::process::exit(1);
+        }
+    };
+    let syllables = args.syllables.or(profile.syllables);
+    let part = args.part.to_owned().or(profile.part);
+    let emphasis = args.emphasis.or(profile.emphasis);
+    let batch_mode = args.word.is_none();
+
+    let words = match collect_words(&args) {
+        Ok(words) => words,
+        Err(err) => {
+            eprintln!("error: {}", err);
+            std::process::exit(1);
+        }
+    };
+
+    let syllable_values: Vec<Option<i8>> = if syllables.unwrap_or(-1) > 0 {
+        vec![syllables]
     } else {
-        futures::future::join_all(
-            (1..=8)
-                .map(|syllables| {
-                    let options = RifmeOptions {
-                        syllables: Some(syllables),
-                        part: args.part.to_owned(),
-                        emphasis: args.emphasis,
-                    };
-                    get_rifme(args.word.to_owned(), options)
-                })
-                .collect::<Vec<_>>(),
-        )
-        .await
-        .into_iter()
-        .map(|result| result.unwrap())
-        .flatten()
-        .collect::<Vec<_>>()
+        (1..=8).map(Some).collect()
     };
-    println!("{}", rhymes.join("\n"));
+
+    let cache_config = CacheConfig {
+        enabled: !args.no_cache,
+        ttl: Duration::from_secs(args.cache_ttl),
+    };
+    let retries = args.retries;
+
+    let mut tasks = Vec::new();
+    for (word_index, word) in words.iter().enumerate() {
+        for &syllables in &syllable_values {
+            let options = RifmeOptions {
+                syllables,
+                part: part.to_owned(),
+                emphasis,
+            };
+            tasks.push((word_index, word.to_owned(), options));
+        }
+    }
+
+    let results: Vec<IndexedRhymeResult> = stream::iter(tasks)
+        .map(|(word_index, word, options)| {
+            let cache_config = &cache_config;
+            let syllables = options.syllables;
+            async move {
+                let result = get_rifme(word, options, cache_config, retries)
+                    .await
+                    .map(|rhymes| RhymeGroup { syllables, rhymes });
+                (word_index, result)
+            }
+        })
+        .buffer_unordered(args.concurrency as usize)
+        .collect()
+        .await;
+
+    let filter_options = args.filter_options();
+    let per_word: Vec<WordRhymeResult> = group_results_by_word(words.len(), results)
+        .into_iter()
+        .map(split_bucket_results)
+        .map(|(groups, errors)| (filter::apply(groups, &filter_options), errors))
+        .collect();
+
+    if !batch_mode {
+        let (groups, errors) = per_word.into_iter().next().unwrap();
+        let prefix = if groups.is_empty() { "error" } else { "warning" };
+        for err in &errors {
+            eprintln!("{}: {}", prefix, err);
+        }
+        if !groups.is_empty() {
+            println!(
+                "{}",
+                output::render(&words[0], part.as_ref(), &groups, &args.output)
+            );
+        }
+        if !errors.is_empty() {
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    let mut entries = Vec::new();
+    let mut had_error = false;
+    for (word, (groups, errors)) in words.into_iter().zip(per_word) {
+        for err in &errors {
+            had_error = true;
+            eprintln!("{}: {}", word, err);
+        }
+        if !groups.is_empty() {
+            entries.push((word, groups));
+        }
+    }
+    println!(
+        "{}",
+        output::render_batch(&entries, part.as_ref(), &args.output)
+    );
+    if had_error {
+        std::process::exit(1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn group(syllables: i8, rhyme: &str) -> RhymeGroup {
+        RhymeGroup {
+            syllables: Some(syllables),
+            rhymes: vec![rhyme.to_string()],
+        }
+    }
+
+    #[test]
+    fn partial_bucket_failure_keeps_other_buckets_for_the_same_word() {
+        let results: Vec<IndexedRhymeResult> = vec![
+            (0, Ok(group(1, "a"))),
+            (0, Err("503 after retries".into())),
+            (0, Ok(group(3, "c"))),
+            (1, Ok(group(1, "d"))),
+        ];
+        let per_word = group_results_by_word(2, results);
+
+        assert_eq!(per_word[0].iter().filter(|r| r.is_ok()).count(), 2);
+        assert_eq!(per_word[0].iter().filter(|r| r.is_err()).count(), 1);
+        assert_eq!(per_word[1].len(), 1);
+    }
+
+    #[test]
+    fn split_bucket_results_separates_and_sorts_successes() {
+        let results: Vec<Result<RhymeGroup, Box<dyn Error>>> = vec![
+            Ok(group(3, "c")),
+            Err("timeout".into()),
+            Ok(group(1, "a")),
+        ];
+        let (groups, errors) = split_bucket_results(results);
+
+        assert_eq!(
+            groups.iter().map(|g| g.syllables).collect::<Vec<_>>(),
+            vec![Some(1), Some(3)]
+        );
+        assert_eq!(errors.len(), 1);
+    }
 }
@@ -0,0 +1,164 @@
+//! Output formatting for fetched rhymes.
+//!
+//! `text` preserves the original newline-joined behavior; `json` and `csv`
+//! keep the per-syllable-count grouping that the 1..=8 fan-out produces,
+//! which a flattened text dump can't express.
+
+use serde::Serialize;
+
+use crate::PartOfSpeech;
+
+#[derive(Clone, Debug, clap::ValueEnum)]
+pub enum OutputFormat {
+    Text,
+    Json,
+    Csv,
+}
+
+impl std::fmt::Display for OutputFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let name = match self {
+            OutputFormat::Text => "text",
+            OutputFormat::Json => "json",
+            OutputFormat::Csv => "csv",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Rhymes found for a single syllable count within one query.
+pub struct RhymeGroup {
+    pub syllables: Option<i8>,
+    pub rhymes: Vec<String>,
+}
+
+#[derive(Serialize)]
+struct JsonGroup<'a> {
+    syllables: Option<i8>,
+    part: Option<&'a PartOfSpeech>,
+    rhymes: &'a [String],
+}
+
+pub fn render(word: &str, part: Option<&PartOfSpeech>, groups: &[RhymeGroup], format: &OutputFormat) -> String {
+    match format {
+        OutputFormat::Text => groups
+            .iter()
+            .flat_map(|group| group.rhymes.iter().cloned())
+            .collect::<Vec<_>>()
+            .join("\n"),
+        OutputFormat::Json => {
+            let json_groups: Vec<JsonGroup> = groups
+                .iter()
+                .map(|group| JsonGroup {
+                    syllables: group.syllables,
+                    part,
+                    rhymes: &group.rhymes,
+                })
+                .collect();
+            serde_json::to_string_pretty(&json_groups).unwrap()
+        }
+        OutputFormat::Csv => {
+            let mut writer = csv::Writer::from_writer(vec![]);
+            writer.write_record(["word", "rhyme", "syllables"]).unwrap();
+            for group in groups {
+                let syllables = group
+                    .syllables
+                    .map(|s| s.to_string())
+                    .unwrap_or_default();
+                for rhyme in &group.rhymes {
+                    writer.write_record([word, rhyme, &syllables]).unwrap();
+                }
+            }
+            String::from_utf8(writer.into_inner().unwrap()).unwrap()
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct JsonWordEntry<'a> {
+    word: &'a str,
+    groups: Vec<JsonGroup<'a>>,
+}
+
+/// Renders results for several input words, grouped per word. Used by
+/// batch mode (`--words-file`/stdin).
+pub fn render_batch(
+    entries: &[(String, Vec<RhymeGroup>)],
+    part: Option<&PartOfSpeech>,
+    format: &OutputFormat,
+) -> String {
+    match format {
+        OutputFormat::Text => entries
+            .iter()
+            .map(|(word, groups)| format!("== {} ==\n{}", word, render(word, part, groups, format)))
+            .collect::<Vec<_>>()
+            .join("\n\n"),
+        OutputFormat::Json => {
+            let json_entries: Vec<JsonWordEntry> = entries
+                .iter()
+                .map(|(word, groups)| JsonWordEntry {
+                    word,
+                    groups: groups
+                        .iter()
+                        .map(|group| JsonGroup {
+                            syllables: group.syllables,
+                            part,
+                            rhymes: &group.rhymes,
+                        })
+                        .collect(),
+                })
+                .collect();
+            serde_json::to_string_pretty(&json_entries).unwrap()
+        }
+        OutputFormat::Csv => {
+            let mut writer = csv::Writer::from_writer(vec![]);
+            writer.write_record(["word", "rhyme", "syllables"]).unwrap();
+            for (word, groups) in entries {
+                for group in groups {
+                    let syllables = group
+                        .syllables
+                        .map(|s| s.to_string())
+                        .unwrap_or_default();
+                    for rhyme in &group.rhymes {
+                        writer.write_record([word.as_str(), rhyme, &syllables]).unwrap();
+                    }
+                }
+            }
+            String::from_utf8(writer.into_inner().unwrap()).unwrap()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_groups() -> Vec<RhymeGroup> {
+        vec![
+            RhymeGroup {
+                syllables: Some(1),
+                rhymes: vec!["привет".into()],
+            },
+            RhymeGroup {
+                syllables: Some(2),
+                rhymes: vec!["рассвет".into(), "банкет".into()],
+            },
+        ]
+    }
+
+    #[test]
+    fn text_flattens_groups_newline_joined() {
+        let out = render("свет", None, &sample_groups(), &OutputFormat::Text);
+        assert_eq!(out, "привет\nрассвет\nбанкет");
+    }
+
+    #[test]
+    fn csv_has_one_row_per_rhyme_with_its_group_syllables() {
+        let out = render("свет", None, &sample_groups(), &OutputFormat::Csv);
+        let mut lines = out.lines();
+        assert_eq!(lines.next(), Some("word,rhyme,syllables"));
+        assert_eq!(lines.next(), Some("свет,привет,1"));
+        assert_eq!(lines.next(), Some("свет,рассвет,2"));
+        assert_eq!(lines.next(), Some("свет,банкет,2"));
+    }
+}